@@ -0,0 +1,173 @@
+//! Two-ref comparison mode: benchmark every configured command on a `base`
+//! and a `head` ref and render a side-by-side before/after report, so
+//! fineregr can gate a PR instead of only walking full history.
+
+use crate::stats::{mean, variance, welch_t_test};
+use serde_derive::Serialize;
+
+/// One command's base-vs-head comparison.
+#[derive(Debug, Serialize)]
+pub struct ComparisonRow {
+    pub command: String,
+    pub base_mean: f64,
+    pub base_stddev: f64,
+    pub head_mean: f64,
+    pub head_stddev: f64,
+    pub ratio: f64,
+    pub p_value: f64,
+    pub significant: bool,
+}
+
+/// Compares `base_times` against `head_times` for `command`, using the same
+/// Welch's t-test and `p_threshold`/`min_change` thresholds as the regression
+/// pass (`regression::detect`). Returns `None` when either side has too few
+/// samples to estimate a variance.
+pub fn compare(
+    command: &str,
+    base_times: &[f64],
+    head_times: &[f64],
+    p_threshold: f64,
+    min_change: f64,
+) -> Option<ComparisonRow> {
+    if base_times.len() < 2 || head_times.len() < 2 {
+        return None;
+    }
+
+    let base_mean = mean(base_times);
+    let head_mean = mean(head_times);
+    let base_var = variance(base_times);
+    let head_var = variance(head_times);
+    let percent_change = (head_mean - base_mean) / base_mean;
+
+    let test = welch_t_test(
+        base_mean,
+        base_var,
+        base_times.len(),
+        head_mean,
+        head_var,
+        head_times.len(),
+    )?;
+
+    Some(ComparisonRow {
+        command: command.to_owned(),
+        base_mean,
+        base_stddev: base_var.sqrt(),
+        head_mean,
+        head_stddev: head_var.sqrt(),
+        ratio: head_mean / base_mean,
+        p_value: test.p_value,
+        significant: test.p_value < p_threshold && percent_change > min_change,
+    })
+}
+
+/// Renders `rows` as a Markdown table suitable for pasting into a PR comment.
+pub fn to_markdown(rows: &[ComparisonRow]) -> String {
+    let mut out = String::new();
+    out.push_str("| Command | Base (mean ± stddev) | Head (mean ± stddev) | Ratio | Significant |\n");
+    out.push_str("|---|---|---|---|---|\n");
+    for row in rows {
+        out.push_str(&format!(
+            "| `{}` | {:.6}s ± {:.6}s | {:.6}s ± {:.6}s | {:.3}x | {} |\n",
+            row.command.replace('`', "\\`"),
+            row.base_mean,
+            row.base_stddev,
+            row.head_mean,
+            row.head_stddev,
+            row.ratio,
+            if row.significant { "⚠️ yes" } else { "no" },
+        ));
+    }
+    out
+}
+
+/// Renders `rows` as a compact HTML section, to be prepended to `index.html`.
+pub fn to_html(rows: &[ComparisonRow]) -> String {
+    let mut out = String::from(
+        "<section class=\"comparison\">\n<h2>Base vs head comparison</h2>\n<table border=\"1\" cellpadding=\"4\">\n\
+         <tr><th>Command</th><th>Base</th><th>Head</th><th>Ratio</th><th>Significant</th></tr>\n",
+    );
+    for row in rows {
+        out.push_str(&format!(
+            "<tr><td>{}</td><td>{:.6}s &plusmn; {:.6}s</td><td>{:.6}s &plusmn; {:.6}s</td><td>{:.3}x</td><td>{}</td></tr>\n",
+            escape_html(&row.command),
+            row.base_mean,
+            row.base_stddev,
+            row.head_mean,
+            row.head_stddev,
+            row.ratio,
+            if row.significant { "yes" } else { "no" },
+        ));
+    }
+    out.push_str("</table>\n</section>\n");
+    out
+}
+
+/// Escapes the characters that are meaningful in HTML text content.
+/// `command` can come from Criterion group/bench names, which in turn come
+/// from files under the benchmarked repository's `target/criterion` tree —
+/// not a string fineregr controls.
+fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(command: &str, significant: bool) -> ComparisonRow {
+        ComparisonRow {
+            command: command.to_owned(),
+            base_mean: 1.0,
+            base_stddev: 0.1,
+            head_mean: 1.2,
+            head_stddev: 0.1,
+            ratio: 1.2,
+            p_value: 0.01,
+            significant,
+        }
+    }
+
+    #[test]
+    fn compare_requires_both_significant_p_value_and_min_change() {
+        let base = [1.0, 1.0001, 0.9999, 1.0, 1.0002, 0.9998];
+        // A clearly significant, but trivially small (0.1%), slowdown.
+        let head = [1.001, 1.0011, 1.0009, 1.001, 1.0012, 1.0008];
+
+        let row = compare("bench", &base, &head, 0.05, 0.1).expect("enough samples on both sides");
+        assert!(row.p_value < 0.05, "the p-value should still read as significant");
+        assert!(!row.significant, "a 0.1% change should not pass a 10% min_change threshold");
+    }
+
+    #[test]
+    fn compare_flags_a_change_that_clears_both_thresholds() {
+        let base = [1.0, 1.01, 0.99, 1.0, 1.02, 0.98];
+        let head = [2.0, 2.01, 1.99, 2.0, 2.02, 1.98];
+
+        let row = compare("bench", &base, &head, 0.05, 0.1).expect("enough samples on both sides");
+        assert!(row.significant);
+    }
+
+    #[test]
+    fn compare_is_none_with_too_few_samples() {
+        assert!(compare("bench", &[1.0], &[1.0, 2.0], 0.05, 0.1).is_none());
+        assert!(compare("bench", &[1.0, 2.0], &[1.0], 0.05, 0.1).is_none());
+    }
+
+    #[test]
+    fn to_html_escapes_command_names() {
+        let rows = [row("<script>alert(1)</script>", true)];
+        let html = to_html(&rows);
+        assert!(!html.contains("<script>"));
+        assert!(html.contains("&lt;script&gt;alert(1)&lt;/script&gt;"));
+    }
+
+    #[test]
+    fn to_markdown_escapes_backticks_in_command_names() {
+        let rows = [row("weird`command", false)];
+        let md = to_markdown(&rows);
+        assert!(md.contains("weird\\`command"));
+    }
+}
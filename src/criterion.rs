@@ -0,0 +1,152 @@
+//! Ingestion of Criterion's `target/criterion/**/new/estimates.json` output,
+//! as an alternative to Hyperfine for projects benchmarked with `cargo criterion`.
+
+use anyhow::{Context, Result};
+use serde_derive::Deserialize;
+use std::path::Path;
+use walkdir::WalkDir;
+
+/// The raw per-iteration measurements Criterion writes next to
+/// `estimates.json`, as `new/sample.json`: `times[i]` is the total elapsed
+/// nanoseconds for running the benchmark `iters[i]` times.
+#[derive(Debug, Deserialize)]
+struct RawSample {
+    iters: Vec<f64>,
+    times: Vec<f64>,
+}
+
+/// One benchmark's measurements, already converted from Criterion's
+/// nanoseconds to the seconds used everywhere else in fineregr.
+pub struct Measurement {
+    /// `"<group>/<bench>"`, matching the `command` field of a Hyperfine result.
+    pub command: String,
+    pub times: Vec<f64>,
+}
+
+/// Walks `<criterion_dir>` (typically `<repo_dir>/target/criterion`) for
+/// `new/estimates.json` files and turns each into a [`Measurement`].
+///
+/// `estimates.json` only locates the benchmark directories; the actual
+/// samples come from the sibling `new/sample.json`, whose `times`/`iters`
+/// arrays give the real per-iteration measurements (`times[i] / iters[i]`).
+/// That keeps `Measurement::times` a genuine sample, as `regression::detect`
+/// and `compare::compare` expect, rather than two unrelated point estimates.
+///
+/// A Criterion bench ID is the slash-separated path to its `new` directory,
+/// e.g. `group/bench/params/new/estimates.json` encodes `group/bench/params`;
+/// we keep only the first two components (`group`, `bench`) as the command,
+/// folding parameterized runs of the same bench into one series. An
+/// ungrouped bench (`group/new/estimates.json`, no `bench` component) keeps
+/// just the group name instead of duplicating it.
+pub fn collect(criterion_dir: &Path) -> Result<Vec<Measurement>> {
+    let mut measurements = Vec::new();
+    if !criterion_dir.is_dir() {
+        return Ok(measurements);
+    }
+
+    for entry in WalkDir::new(criterion_dir) {
+        let path = entry?.into_path();
+        if path.file_name().and_then(|n| n.to_str()) != Some("estimates.json") {
+            continue;
+        }
+        // Only the `new/estimates.json` copy reflects the latest run; `base/`
+        // holds the previous one, which Criterion itself uses for comparisons.
+        if path.parent().and_then(|p| p.file_name()).and_then(|n| n.to_str()) != Some("new") {
+            continue;
+        }
+
+        let bench_id = bench_id(criterion_dir, &path)?;
+        let mut parts = bench_id.splitn(3, '/');
+        let group = parts.next().context("missing group in Criterion bench id")?;
+        let command = match parts.next() {
+            Some(bench) => format!("{}/{}", group, bench),
+            None => group.to_owned(),
+        };
+
+        let sample_path = path.with_file_name("sample.json");
+        let sample: RawSample = serde_json::from_reader(std::fs::File::open(&sample_path)?)
+            .with_context(|| format!("deserializing {:?}", sample_path))?;
+
+        let times: Vec<f64> = sample
+            .times
+            .iter()
+            .zip(&sample.iters)
+            .map(|(&total_ns, &iters)| ns_to_secs(total_ns / iters))
+            .collect();
+
+        measurements.push(Measurement { command, times });
+    }
+
+    Ok(measurements)
+}
+
+fn ns_to_secs(ns: f64) -> f64 {
+    ns / 1_000_000_000.0
+}
+
+/// Recovers the slash-joined bench id (`group/bench/params`) from the path to
+/// its `new/estimates.json` file.
+fn bench_id(criterion_dir: &Path, estimates_path: &Path) -> Result<String> {
+    let relative = estimates_path
+        .strip_prefix(criterion_dir)
+        .context("estimates.json outside of the criterion directory")?;
+    let components: Vec<&str> = relative
+        .components()
+        .filter_map(|c| c.as_os_str().to_str())
+        .collect();
+    // Drop the trailing `new/estimates.json`.
+    let bench_components = &components[..components.len().saturating_sub(2)];
+    Ok(bench_components.join("/"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn write_bench(criterion_dir: &Path, bench_path: &str) {
+        let new_dir = criterion_dir.join(bench_path).join("new");
+        fs::create_dir_all(&new_dir).unwrap();
+        fs::write(
+            new_dir.join("estimates.json"),
+            r#"{"mean":{"point_estimate":123.0},"slope":{"point_estimate":456.0}}"#,
+        )
+        .unwrap();
+        fs::write(
+            new_dir.join("sample.json"),
+            r#"{"iters":[1.0,2.0],"times":[1000000000.0,4000000000.0]}"#,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn collects_real_per_iteration_samples() {
+        let dir = tempdir::TempDir::new("fineregr-criterion-test").unwrap();
+        write_bench(dir.path(), "mygroup/mybench");
+
+        let measurements = collect(dir.path()).unwrap();
+        assert_eq!(measurements.len(), 1);
+        assert_eq!(measurements[0].command, "mygroup/mybench");
+        assert_eq!(measurements[0].times, vec![1.0, 2.0]);
+    }
+
+    #[test]
+    fn ungrouped_bench_does_not_duplicate_its_name() {
+        let dir = tempdir::TempDir::new("fineregr-criterion-test").unwrap();
+        write_bench(dir.path(), "mybench");
+
+        let measurements = collect(dir.path()).unwrap();
+        assert_eq!(measurements.len(), 1);
+        assert_eq!(measurements[0].command, "mybench");
+    }
+
+    #[test]
+    fn ignores_the_base_directory() {
+        let dir = tempdir::TempDir::new("fineregr-criterion-test").unwrap();
+        let base_dir = dir.path().join("mygroup/mybench/base");
+        fs::create_dir_all(&base_dir).unwrap();
+        fs::write(base_dir.join("estimates.json"), "{}").unwrap();
+
+        assert!(collect(dir.path()).unwrap().is_empty());
+    }
+}
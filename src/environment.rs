@@ -0,0 +1,208 @@
+//! Optional machine-stabilization hooks. Fine-grained history comparisons
+//! are easily swamped by noise from a kernel that's free to reclock cores or
+//! migrate a process between them; this lets users pin a run to specific
+//! cores and fix the frequency governor/turbo state for its duration.
+
+use anyhow::Context;
+use anyhow::Result;
+use serde_derive::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+
+const BOOST_PATH: &str = "/sys/devices/system/cpu/cpufreq/boost";
+
+/// The `environment` block of the config file.
+#[derive(Debug, Deserialize)]
+pub struct Environment {
+    /// CPU cores to pin every prepare/benchmark invocation to, via `taskset -c`.
+    pub cores: Option<Vec<usize>>,
+    /// CPU frequency governor to set for the run's duration, e.g. `"performance"`.
+    pub governor: Option<String>,
+    /// Disable turbo/boost for the run's duration.
+    #[serde(default)]
+    pub disable_boost: bool,
+}
+
+impl Environment {
+    /// Prefixes `program`/`args` with `taskset -c <cores>` when `self.cores`
+    /// is set, otherwise returns them unchanged.
+    pub fn pin(&self, program: &str, args: &[&str]) -> (String, Vec<String>) {
+        match &self.cores {
+            Some(cores) if !cores.is_empty() => {
+                let list = cores.iter().map(usize::to_string).collect::<Vec<_>>().join(",");
+                let mut full = vec!["-c".to_owned(), list, program.to_owned()];
+                full.extend(args.iter().map(|s| s.to_string()));
+                ("taskset".to_owned(), full)
+            }
+            _ => (program.to_owned(), args.iter().map(|s| s.to_string()).collect()),
+        }
+    }
+
+    /// Applies the configured governor and boost settings, returning a guard
+    /// that restores whatever was observed beforehand once dropped. Fails
+    /// with a clear error if a requested step can't be applied, e.g. no
+    /// permission to write the sysfs boost toggle.
+    pub fn stabilize(&self) -> Result<StabilizationGuard> {
+        let previous_governors = read_governors()?;
+        if let Some(governor) = &self.governor {
+            write_governor(governor)?;
+        }
+
+        let previous_boost = read_boost()?;
+        if self.disable_boost {
+            write_boost(false)?;
+        }
+
+        Ok(StabilizationGuard {
+            previous_governors,
+            previous_boost,
+        })
+    }
+
+    /// Snapshots the conditions the benchmark is about to run under, to be
+    /// recorded alongside the result so the plot tooltip can flag
+    /// inconsistencies across commits.
+    pub fn observe(&self) -> MachineConditions {
+        MachineConditions {
+            governor: read_governor().ok().flatten(),
+            boost_enabled: read_boost().ok().flatten(),
+            cores: self.cores.clone(),
+            cpu_model: cpu_model(),
+        }
+    }
+}
+
+/// Restores the machine's previous governor/boost settings when dropped.
+pub struct StabilizationGuard {
+    /// Each core's governor before `stabilize` ran, keyed by its
+    /// `scaling_governor` path, so a heterogeneous machine (different
+    /// pre-existing governors per core) is restored exactly rather than
+    /// flattened to whatever one core happened to have.
+    previous_governors: HashMap<PathBuf, String>,
+    previous_boost: Option<bool>,
+}
+
+impl Drop for StabilizationGuard {
+    fn drop(&mut self) {
+        if let Err(e) = write_governors(&self.previous_governors) {
+            eprintln!("failed to restore CPU governor(s): {:?}", e);
+        }
+        if let Some(boost) = self.previous_boost {
+            if let Err(e) = write_boost(boost) {
+                eprintln!("failed to restore turbo boost state: {:?}", e);
+            }
+        }
+    }
+}
+
+/// Conditions observed on the machine for a single benchmark run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MachineConditions {
+    pub governor: Option<String>,
+    pub boost_enabled: Option<bool>,
+    pub cores: Option<Vec<usize>>,
+    pub cpu_model: Option<String>,
+}
+
+impl fmt::Display for MachineConditions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "governor={} boost={} cores={} cpu={}",
+            self.governor.as_deref().unwrap_or("?"),
+            self.boost_enabled.map(|b| b.to_string()).unwrap_or_else(|| "?".to_owned()),
+            self.cores
+                .as_ref()
+                .map(|c| c.iter().map(usize::to_string).collect::<Vec<_>>().join(","))
+                .unwrap_or_else(|| "all".to_owned()),
+            self.cpu_model.as_deref().unwrap_or("?"),
+        )
+    }
+}
+
+fn cpu_governor_paths() -> Result<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+    for entry in fs::read_dir("/sys/devices/system/cpu").context("listing /sys/devices/system/cpu")? {
+        let entry = entry?;
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        if let Some(rest) = name.strip_prefix("cpu") {
+            if !rest.is_empty() && rest.chars().all(|c| c.is_ascii_digit()) {
+                let governor_path = entry.path().join("cpufreq/scaling_governor");
+                if governor_path.is_file() {
+                    paths.push(governor_path);
+                }
+            }
+        }
+    }
+    Ok(paths)
+}
+
+fn read_governor() -> Result<Option<String>> {
+    let paths = cpu_governor_paths()?;
+    match paths.first() {
+        Some(path) => Ok(Some(fs::read_to_string(path)?.trim().to_owned())),
+        None => Ok(None),
+    }
+}
+
+/// Snapshots every core's governor, keyed by its `scaling_governor` path, so
+/// they can each be restored individually even on a machine where they
+/// didn't all start out the same.
+fn read_governors() -> Result<HashMap<PathBuf, String>> {
+    cpu_governor_paths()?
+        .into_iter()
+        .map(|path| {
+            let governor = fs::read_to_string(&path)?.trim().to_owned();
+            Ok((path, governor))
+        })
+        .collect()
+}
+
+fn write_governor(governor: &str) -> Result<()> {
+    let paths = cpu_governor_paths()?;
+    if paths.is_empty() {
+        anyhow::bail!("no cpufreq scaling_governor files found under /sys/devices/system/cpu; cannot set governor {:?}", governor);
+    }
+    for path in paths {
+        fs::write(&path, governor)
+            .with_context(|| format!("writing governor {:?} to {:?} (are you root?)", governor, path))?;
+    }
+    Ok(())
+}
+
+/// Restores each core's governor to the value previously snapshotted by
+/// [`read_governors`].
+fn write_governors(governors: &HashMap<PathBuf, String>) -> Result<()> {
+    for (path, governor) in governors {
+        fs::write(path, governor)
+            .with_context(|| format!("restoring governor {:?} to {:?} (are you root?)", governor, path))?;
+    }
+    Ok(())
+}
+
+fn read_boost() -> Result<Option<bool>> {
+    match fs::read_to_string(BOOST_PATH) {
+        Ok(s) => Ok(Some(s.trim() == "1")),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e).context("reading turbo boost state"),
+    }
+}
+
+fn write_boost(enabled: bool) -> Result<()> {
+    let value = if enabled { "1" } else { "0" };
+    fs::write(BOOST_PATH, value)
+        .with_context(|| format!("writing {:?} to {} (are you root?)", value, BOOST_PATH))
+}
+
+fn cpu_model() -> Option<String> {
+    let cpuinfo = fs::read_to_string("/proc/cpuinfo").ok()?;
+    cpuinfo
+        .lines()
+        .find(|l| l.starts_with("model name"))
+        .and_then(|l| l.split(':').nth(1))
+        .map(|s| s.trim().to_owned())
+}
@@ -0,0 +1,176 @@
+//! In-process git access via `git2`, replacing the old approach of shelling
+//! out to a `git` binary for every piece of commit metadata.
+
+use anyhow::{Context, Result};
+use git2::build::RepoBuilder;
+use git2::{FetchOptions, Oid, RemoteCallbacks, Repository, Sort};
+use indicatif::{ProgressBar, ProgressStyle};
+use std::path::Path;
+
+/// The git operations fineregr needs. A trait so the metadata-heavy parts of
+/// `run`/`plot` don't hard-depend on a particular git implementation.
+pub trait GitBackend {
+    /// Checks out `sha` in the working tree.
+    fn checkout(&self, sha: &str) -> Result<()>;
+    /// Lists the commit SHAs reachable from `branch`, most recent first.
+    fn commits(&self, branch: &str) -> Result<Vec<String>>;
+    fn commit_date(&self, sha: &str) -> Result<String>;
+    fn commit_message(&self, sha: &str) -> Result<String>;
+    /// Paths touched by `sha`, relative to the repository root, compared
+    /// against its first parent (or the empty tree for a root commit).
+    fn changed_paths(&self, sha: &str) -> Result<Vec<String>>;
+}
+
+fn progress_bar(len: u64, message: &'static str) -> ProgressBar {
+    let bar = ProgressBar::new(len);
+    bar.set_style(
+        ProgressStyle::with_template("{msg} [{bar:40}] {pos}/{len}")
+            .unwrap()
+            .progress_chars("=> "),
+    );
+    bar.set_message(message);
+    bar
+}
+
+/// A [`GitBackend`] backed by an in-process `libgit2` repository.
+pub struct Git2Backend {
+    repo: Repository,
+}
+
+impl Git2Backend {
+    /// Clones `remote` into `repo_dir` if it isn't already a checkout,
+    /// otherwise fetches the latest history from `origin`. Either way the
+    /// working tree ends up on `main`.
+    pub fn open_or_update(remote: &str, repo_dir: &Path) -> Result<Self> {
+        let repo = if repo_dir.is_dir() {
+            let repo = Repository::open(repo_dir).context("opening existing repository")?;
+            let bar = progress_bar(0, "fetching");
+            let mut callbacks = RemoteCallbacks::new();
+            callbacks.transfer_progress(|p| {
+                bar.set_length(p.total_objects() as u64);
+                bar.set_position(p.received_objects() as u64);
+                true
+            });
+            let mut fetch_options = FetchOptions::new();
+            fetch_options.remote_callbacks(callbacks);
+            repo.find_remote("origin")
+                .context("finding origin remote")?
+                .fetch(&["main"], Some(&mut fetch_options), None)
+                .context("fetching origin")?;
+            bar.finish_and_clear();
+
+            let fetched = repo
+                .find_reference("refs/remotes/origin/main")
+                .context("resolving fetched origin/main")?
+                .peel_to_commit()
+                .context("peeling origin/main to a commit")?;
+            repo.reference(
+                "refs/heads/main",
+                fetched.id(),
+                true,
+                "fast-forward to origin/main",
+            )
+            .context("fast-forwarding local main")?;
+
+            repo
+        } else {
+            println!("Cloning {} to {:?}", remote, repo_dir);
+            let bar = progress_bar(0, "cloning");
+            let mut callbacks = RemoteCallbacks::new();
+            callbacks.transfer_progress(|p| {
+                bar.set_length(p.total_objects() as u64);
+                bar.set_position(p.received_objects() as u64);
+                true
+            });
+            let mut fetch_options = FetchOptions::new();
+            fetch_options.remote_callbacks(callbacks);
+            let repo = RepoBuilder::new()
+                .fetch_options(fetch_options)
+                .clone(remote, repo_dir)
+                .context("cloning repository")?;
+            bar.finish_and_clear();
+            repo
+        };
+
+        let backend = Self { repo };
+        backend.checkout("main")?;
+        Ok(backend)
+    }
+
+    fn resolve(&self, sha: &str) -> Result<Oid> {
+        self.repo
+            .revparse_single(sha)
+            .with_context(|| format!("resolving {}", sha))
+            .map(|obj| obj.id())
+    }
+}
+
+impl GitBackend for Git2Backend {
+    fn checkout(&self, sha: &str) -> Result<()> {
+        let oid = self.resolve(sha)?;
+        let commit = self.repo.find_commit(oid)?;
+        self.repo
+            .checkout_tree(commit.as_object(), None)
+            .with_context(|| format!("checking out {}", sha))?;
+        self.repo.set_head_detached(oid)?;
+        Ok(())
+    }
+
+    fn commits(&self, branch: &str) -> Result<Vec<String>> {
+        let start = self.resolve(branch)?;
+        let mut revwalk = self.repo.revwalk()?;
+        revwalk.set_sorting(Sort::TIME)?;
+        revwalk.push(start)?;
+
+        let bar = progress_bar(0, "walking commits");
+        let mut shas = Vec::new();
+        for oid in revwalk {
+            let oid = oid?;
+            shas.push(oid.to_string());
+            bar.inc(1);
+        }
+        bar.finish_and_clear();
+        Ok(shas)
+    }
+
+    fn commit_date(&self, sha: &str) -> Result<String> {
+        let oid = self.resolve(sha)?;
+        let commit = self.repo.find_commit(oid)?;
+        let time = commit.time();
+        let datetime = chrono::DateTime::from_timestamp(time.seconds(), 0)
+            .context("converting commit time")?;
+        Ok(datetime.to_rfc3339())
+    }
+
+    fn commit_message(&self, sha: &str) -> Result<String> {
+        let oid = self.resolve(sha)?;
+        let commit = self.repo.find_commit(oid)?;
+        Ok(commit.message().unwrap_or_default().to_owned())
+    }
+
+    fn changed_paths(&self, sha: &str) -> Result<Vec<String>> {
+        let oid = self.resolve(sha)?;
+        let commit = self.repo.find_commit(oid)?;
+        let tree = commit.tree()?;
+        let parent_tree = commit.parents().next().map(|p| p.tree()).transpose()?;
+
+        let diff = self
+            .repo
+            .diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), None)
+            .with_context(|| format!("diffing {}", sha))?;
+
+        let mut paths = Vec::new();
+        diff.foreach(
+            &mut |delta, _| {
+                if let Some(path) = delta.new_file().path().or_else(|| delta.old_file().path()) {
+                    paths.push(path.to_string_lossy().into_owned());
+                }
+                true
+            },
+            None,
+            None,
+            None,
+        )?;
+        Ok(paths)
+    }
+}
@@ -2,14 +2,48 @@ use anyhow::{bail, Context, Result};
 use serde_derive::{Deserialize, Serialize};
 use serde_json::json;
 use sha2::{Digest, Sha256};
+use std::cell::OnceCell;
+use std::collections::HashMap;
 use std::fs::File;
 use std::io::prelude::*;
-use std::path::PathBuf;
-use std::process::{Command, Stdio};
-use std::str::FromStr;
+use std::path::{Path, PathBuf};
+use std::process::Command;
 use walkdir::WalkDir;
 
+mod compare;
+mod criterion;
+mod environment;
+mod git;
+mod pathtrie;
+mod regression;
+mod shellcmd;
+mod stats;
+
+use environment::{Environment, MachineConditions};
+use git::{Git2Backend, GitBackend};
+use pathtrie::PathTrie;
+use regression::Sample;
+
+/// Which tool produces the timing data for each benchmark entry.
+#[derive(Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+enum Engine {
+    /// `self.benchmarks` are Hyperfine-compatible command lines, run directly.
+    #[default]
+    Hyperfine,
+    /// `self.benchmarks` are commands that populate `target/criterion` (e.g.
+    /// `cargo criterion`), whose output is then walked and ingested.
+    Criterion,
+}
+
+/// `compare` block of the config file: two-ref comparison mode.
 #[derive(Debug, Deserialize)]
+struct CompareConfig {
+    base: String,
+    head: String,
+}
+
+#[derive(Deserialize)]
 struct Benchmarker {
     /// The Git repository to benchmark
     repository: String,
@@ -21,6 +55,26 @@ struct Benchmarker {
     #[serde(default = "Benchmarker::tmp_dir")]
     repo_dir: String,
     num_commits: Option<usize>,
+    /// Only benchmark commits that touch one of these path prefixes (e.g.
+    /// `src/engine`). Unset means every commit is benchmarked.
+    paths: Option<Vec<String>>,
+    /// Machine-stabilization settings applied for the run's duration.
+    environment: Option<Environment>,
+    /// When set, `run` benchmarks `compare.base` and `compare.head` only and
+    /// produces a tabular diff, instead of walking the whole history of `main`.
+    compare: Option<CompareConfig>,
+    /// Which benchmarking tool produced the `benchmarks` commands.
+    #[serde(default)]
+    engine: Engine,
+    /// One-sided p-value below which a slowdown is considered statistically real.
+    #[serde(default = "Benchmarker::default_regression_p_threshold")]
+    regression_p_threshold: f64,
+    /// Minimum relative slowdown, e.g. `0.05` for 5%, required to flag a regression.
+    #[serde(default = "Benchmarker::default_regression_min_change")]
+    regression_min_change: f64,
+    /// Lazily-initialized in-process git backend for `repo_dir`.
+    #[serde(skip)]
+    git: OnceCell<Git2Backend>,
 }
 
 impl Benchmarker {
@@ -32,6 +86,14 @@ impl Benchmarker {
         toml::from_str(&s).context("deserializing configuration")
     }
 
+    fn default_regression_p_threshold() -> f64 {
+        0.05
+    }
+
+    fn default_regression_min_change() -> f64 {
+        0.05
+    }
+
     fn tmp_dir() -> String {
         tempdir::TempDir::new("fineregr")
             .expect("error creating temprary directory")
@@ -41,42 +103,53 @@ impl Benchmarker {
             .to_owned()
     }
 
-    /// Clones the repository as a subdirectory of the current working directory
-    fn clone_repo(&self) -> Result<()> {
-        if PathBuf::from_str(&self.repo_dir)?.is_dir() {
-            println!("Pulling latest changes from {}", self.repository);
-            Command::new("git")
-                .arg("checkout")
-                .arg("main")
-                .current_dir(&self.repo_dir)
-                .spawn()?
-                .wait()?;
-            Command::new("git")
-                .arg("pull")
-                .current_dir(&self.repo_dir)
-                .spawn()?
-                .wait()?;
-        } else {
-            println!("Cloning {} to {}", self.repository, self.repo_dir);
-            Command::new("git")
-                .arg("clone")
-                .arg(&self.repository)
-                .arg(&self.repo_dir)
-                .spawn()?
-                .wait()?;
+    /// Returns the in-process git backend. The first call clones the
+    /// repository (or fetches the latest history into an existing clone);
+    /// later calls reuse that backend without fetching again.
+    fn git(&self) -> Result<&Git2Backend> {
+        if self.git.get().is_none() {
+            let backend = Git2Backend::open_or_update(&self.repository, Path::new(&self.repo_dir))?;
+            let _ = self.git.set(backend);
+        }
+        Ok(self.git.get().expect("just initialized"))
+    }
+
+    /// Pins `program`/`args` to the configured cores, if any.
+    fn pin<'a>(&self, program: &'a str, args: &[&'a str]) -> (String, Vec<String>) {
+        match &self.environment {
+            Some(env) => env.pin(program, args),
+            None => (program.to_owned(), args.iter().map(|s| s.to_string()).collect()),
         }
+    }
 
-        Ok(())
+    /// Tokenizes `line` with real shell-quoting rules, resolves the program
+    /// against `PATH`, then pins the resolved path to the configured cores
+    /// if any, and returns a ready-to-spawn `Command` rooted at
+    /// `self.repo_dir`.
+    ///
+    /// Resolution has to happen *before* pinning: `pin` can fold `program`
+    /// into `taskset`'s argv, and `taskset` resolves that argument itself via
+    /// `execvp` without going through `shellcmd::resolve`'s PATH sanitizing.
+    /// Pinning a bare name would let a file the cloned repository dropped in
+    /// `self.repo_dir` shadow the intended system binary.
+    fn build_command(&self, line: &str) -> Result<Command> {
+        let tokens = shellcmd::tokenize(line)?;
+        let (program, args) = tokens.split_first().with_context(|| format!("empty command: {:?}", line))?;
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+
+        let resolved_program = shellcmd::resolve(program)?;
+        let resolved_program = resolved_program.to_str().context("resolved program path is not valid UTF-8")?;
+        let (program, args) = self.pin(resolved_program, &args);
+        let resolved = shellcmd::resolve(&program)?;
+
+        let mut cmd = Command::new(resolved);
+        cmd.args(args).current_dir(&self.repo_dir);
+        Ok(cmd)
     }
 
     fn run_prepare(&self) -> Result<()> {
         for cmd in &self.prepare {
-            let args: Vec<&str> = cmd.split_whitespace().collect();
-            let ret = Command::new(args[0])
-                .args(&args[1..])
-                .current_dir(&self.repo_dir)
-                .spawn()?
-                .wait()?;
+            let ret = self.build_command(cmd)?.spawn()?.wait()?;
             if !ret.success() {
                 bail!("return code {:?}", ret);
             }
@@ -84,70 +157,141 @@ impl Benchmarker {
         Ok(())
     }
 
-    fn checkout(&self, sha: &str) -> Result<()> {
-        Command::new("git")
-            .arg("checkout")
-            .arg(sha)
+    /// Runs `bench` through Hyperfine, exporting its result directly to
+    /// `json_file`, then stamps the observed machine conditions onto it.
+    fn run_hyperfine(&self, bench: &str, json_file: &PathBuf, conditions: Option<&MachineConditions>) -> Result<bool> {
+        let json_file_str = json_file.to_str().context("json output path is not valid UTF-8")?;
+        let resolved_hyperfine = shellcmd::resolve("hyperfine")?;
+        let resolved_hyperfine = resolved_hyperfine
+            .to_str()
+            .context("resolved program path is not valid UTF-8")?;
+        let (program, args) = self.pin(resolved_hyperfine, &["--export-json", json_file_str, "--warmup", "1", bench]);
+        let resolved = shellcmd::resolve(&program)?;
+        let res = Command::new(resolved)
+            .args(args)
             .current_dir(&self.repo_dir)
             .spawn()?
             .wait()?;
-        Ok(())
+        if !res.success() {
+            return Ok(false);
+        }
+        if let Some(conditions) = conditions {
+            self.stamp_environment(json_file, conditions)?;
+        }
+        Ok(true)
     }
 
-    fn get_commits(&self) -> Result<Vec<String>> {
-        let output = Command::new("git")
-            .arg("rev-list")
-            .arg("main")
-            .current_dir(&self.repo_dir)
-            .stdout(Stdio::piped())
-            .spawn()?
-            .wait_with_output()?;
-        Ok(String::from_utf8(output.stdout)?
-            .lines()
-            .map(|l| l.to_owned())
-            .collect())
+    /// Runs `bench` (e.g. `cargo criterion`) and ingests whatever it left
+    /// behind in `target/criterion`, writing it out in the same
+    /// `ResultFile` shape Hyperfine produces so `plot` doesn't need to care
+    /// which engine ran.
+    fn run_criterion(&self, bench: &str, json_file: &PathBuf, conditions: Option<&MachineConditions>) -> Result<bool> {
+        let res = self.build_command(bench)?.spawn()?.wait()?;
+        if !res.success() {
+            return Ok(false);
+        }
+
+        let criterion_dir = PathBuf::from(&self.repo_dir).join("target").join("criterion");
+        let measurements = criterion::collect(&criterion_dir)?;
+        let results: Vec<ResultEntry> = measurements
+            .into_iter()
+            .map(|m| ResultEntry {
+                command: m.command,
+                times: Some(m.times),
+            })
+            .collect();
+        let mut f = File::create(json_file)?;
+        write!(
+            f,
+            "{}",
+            serde_json::to_string(&ResultFile {
+                results,
+                environment: conditions.cloned(),
+            })?
+        )?;
+        Ok(true)
     }
 
-    fn commit_date(&self, sha: &str) -> Result<String> {
-        let output = Command::new("git")
-            .arg("log")
-            .arg("--format=%ci")
-            .arg("-n")
-            .arg("1")
-            .arg(sha)
-            .current_dir(&self.repo_dir)
-            .stdout(Stdio::piped())
-            .spawn()?
-            .wait_with_output()?;
-        String::from_utf8(output.stdout).context("decoding git message")
+    /// Attaches the observed machine conditions to a result file written by
+    /// an external tool (Hyperfine), which has no notion of them.
+    fn stamp_environment(&self, json_file: &PathBuf, conditions: &MachineConditions) -> Result<()> {
+        let mut rf: ResultFile =
+            serde_json::from_reader(File::open(json_file)?).context("reading exported result file")?;
+        rf.environment = Some(conditions.clone());
+        let mut f = File::create(json_file)?;
+        write!(f, "{}", serde_json::to_string(&rf)?)?;
+        Ok(())
     }
 
-    fn commit_message(&self, sha: &str) -> Result<String> {
-        let output = Command::new("git")
-            .arg("log")
-            .arg("--format=%B")
-            .arg("-n")
-            .arg("1")
-            .arg(sha)
-            .current_dir(&self.repo_dir)
-            .stdout(Stdio::piped())
-            .spawn()?
-            .wait_with_output()?;
-        String::from_utf8(output.stdout).context("decoding git message")
+    /// Drops commits that don't touch any of `self.paths`, caching each
+    /// retain/skip decision in `<out_dir>/path_filter_cache.<paths digest>.json`
+    /// so re-runs don't recompute the diff for commits we've already
+    /// classified. The cache file is namespaced by a digest of `self.paths`
+    /// itself, so changing the configured paths starts from a fresh cache
+    /// instead of reusing decisions computed against the old list.
+    fn filter_by_path(&self, shas: Vec<String>, out_dir: &Path) -> Result<Vec<String>> {
+        let Some(paths) = &self.paths else {
+            return Ok(shas);
+        };
+        let trie = PathTrie::from_prefixes(paths);
+
+        let mut paths_digest = Sha256::new();
+        for path in paths {
+            paths_digest.update(path.as_bytes());
+            paths_digest.update(b"\0");
+        }
+        let paths_digest = format!("{:x}", paths_digest.finalize());
+
+        let cache_path = out_dir.join(format!("path_filter_cache.{}.json", paths_digest));
+        let mut cache: HashMap<String, bool> = if cache_path.is_file() {
+            serde_json::from_reader(File::open(&cache_path)?).context("reading path filter cache")?
+        } else {
+            HashMap::new()
+        };
+
+        let mut retained = Vec::new();
+        for sha in shas {
+            let keep = match cache.get(&sha) {
+                Some(keep) => *keep,
+                None => {
+                    let keep = self
+                        .git()?
+                        .changed_paths(&sha)?
+                        .iter()
+                        .any(|changed| trie.matches(changed));
+                    cache.insert(sha.clone(), keep);
+                    keep
+                }
+            };
+            if keep {
+                retained.push(sha);
+            }
+        }
+
+        let mut f = File::create(&cache_path)?;
+        write!(f, "{}", serde_json::to_string(&cache)?)?;
+        Ok(retained)
     }
 
     fn run(&self) -> Result<()> {
-        self.clone_repo()?;
+        self.git()?;
         let out_dir = std::env::current_dir()?.join("results");
         if !out_dir.is_dir() {
             std::fs::create_dir_all(&out_dir)?;
         }
-        for sha in self
-            .get_commits()?
-            .into_iter()
-            .take(self.num_commits.unwrap_or(usize::MAX))
-        {
-            self.checkout(&sha)?;
+
+        // Held for the rest of the run: restores the machine's previous
+        // governor/boost settings on drop, even if we bail out early.
+        let _stabilization = self.environment.as_ref().map(|e| e.stabilize()).transpose()?;
+        let conditions = self.environment.as_ref().map(|e| e.observe());
+
+        if let Some(compare) = &self.compare {
+            return self.run_compare(compare, conditions.as_ref());
+        }
+
+        let shas = self.filter_by_path(self.git()?.commits("main")?, &out_dir)?;
+        for sha in shas.into_iter().take(self.num_commits.unwrap_or(usize::MAX)) {
+            self.git()?.checkout(&sha)?;
 
             for bench in &self.benchmarks {
                 let mut bench_sha = Sha256::new();
@@ -161,34 +305,29 @@ impl Benchmarker {
 
                 if !json_file.is_file() {
                     let success = match self.run_prepare() {
-                        Ok(()) => {
-                            let res = Command::new("hyperfine")
-                                .arg("--export-json")
-                                .arg(&json_file)
-                                .arg("--warmup")
-                                .arg("1")
-                                .arg(bench)
-                                .current_dir(&self.repo_dir)
-                                .spawn()?
-                                .wait()?;
-                            res.success()
-                        }
+                        Ok(()) => match self.engine {
+                            Engine::Hyperfine => self.run_hyperfine(bench, &json_file, conditions.as_ref())?,
+                            Engine::Criterion => self.run_criterion(bench, &json_file, conditions.as_ref())?,
+                        },
                         Err(e) => {
                             eprintln!("{:?}", e);
                             false
                         }
                     };
                     if !success {
-                        let json_data = json!({
+                        let mut json_data = json!({
                             "results": [
                                 {
                                     "command": bench,
                                     "git_sha": sha,
-                                    "git_msg": self.commit_message(&sha)?,
-                                    "git_date": self.commit_date(&sha)?,
+                                    "git_msg": self.git()?.commit_message(&sha)?,
+                                    "git_date": self.git()?.commit_date(&sha)?,
                                 }
                             ]
                         });
+                        if let Some(conditions) = &conditions {
+                            json_data["environment"] = serde_json::to_value(conditions)?;
+                        }
                         let mut f = File::create(json_file)?;
                         write!(f, "{}", json_data)?;
                     }
@@ -205,11 +344,91 @@ impl Benchmarker {
         Ok(())
     }
 
+    /// Benchmarks every configured command on `compare.base` and
+    /// `compare.head` only, then writes a tabular before/after report
+    /// instead of walking the whole history of `main`. The HTML report is
+    /// written to `compare/index.html`, followed by a copy of
+    /// `results/index.html` (the history plot `run` writes) if one is
+    /// present; `results/index.html` itself is left unchanged.
+    fn run_compare(&self, compare: &CompareConfig, conditions: Option<&MachineConditions>) -> Result<()> {
+        let out_dir = std::env::current_dir()?.join("compare");
+        if !out_dir.is_dir() {
+            std::fs::create_dir_all(&out_dir)?;
+        }
+
+        let mut times_by_command: HashMap<String, (Vec<f64>, Vec<f64>)> = HashMap::new();
+        for (is_head, sha) in [(false, &compare.base), (true, &compare.head)] {
+            self.git()?.checkout(sha)?;
+            let label = if is_head { "head" } else { "base" };
+
+            for bench in &self.benchmarks {
+                self.run_prepare()?;
+                let json_file = out_dir.join(format!("{}-{}.json", label, sha));
+                let success = match self.engine {
+                    Engine::Hyperfine => self.run_hyperfine(bench, &json_file, conditions)?,
+                    Engine::Criterion => self.run_criterion(bench, &json_file, conditions)?,
+                };
+                if !success {
+                    bail!("benchmark {:?} failed on {} ({})", bench, label, sha);
+                }
+
+                let rf: ResultFile =
+                    serde_json::from_reader(File::open(&json_file)?).context("reading benchmark result")?;
+                for res in rf.results {
+                    let times = res.times.unwrap_or_default();
+                    let entry = times_by_command.entry(res.command).or_default();
+                    if is_head {
+                        entry.1.extend(times);
+                    } else {
+                        entry.0.extend(times);
+                    }
+                }
+            }
+        }
+
+        let mut rows: Vec<compare::ComparisonRow> = times_by_command
+            .into_iter()
+            .filter_map(|(command, (base_times, head_times))| {
+                compare::compare(
+                    &command,
+                    &base_times,
+                    &head_times,
+                    self.regression_p_threshold,
+                    self.regression_min_change,
+                )
+            })
+            .collect();
+        rows.sort_by(|a, b| a.command.cmp(&b.command));
+
+        let mut f = File::create(out_dir.join("comparison.json"))?;
+        write!(f, "{}", serde_json::to_string_pretty(&rows)?)?;
+
+        let mut f = File::create(out_dir.join("comparison.md"))?;
+        write!(f, "{}", compare::to_markdown(&rows))?;
+
+        // Prepend the comparison section to the history plot's index.html, if
+        // `run` has produced one alongside this `compare` run; otherwise the
+        // comparison section stands on its own.
+        let plot_index = std::env::current_dir()?.join("results").join("index.html");
+        let existing_plot = std::fs::read_to_string(&plot_index).unwrap_or_default();
+        let mut f = File::create(out_dir.join("index.html"))?;
+        write!(f, "{}{}", compare::to_html(&rows), existing_plot)?;
+
+        Ok(())
+    }
+
     fn plot(&self, out_dir: &PathBuf) -> Result<()> {
-        let mut plotdata: Vec<PlotData> = Vec::new();
-        for json_path in WalkDir::new(&out_dir) {
+        // One entry per (git_sha, command) result: its commit metadata, raw
+        // times (if any), and machine conditions, so we can both flatten into
+        // per-point `PlotData` and run the regression pass per command.
+        let mut by_commit: Vec<CommitEntry> = Vec::new();
+        for json_path in WalkDir::new(out_dir) {
             let json_path = json_path?.into_path();
+            // Result files live in per-benchmark subdirectories; the `.json`
+            // files directly in `out_dir` (`regressions.json`, the path filter
+            // cache) are not per-commit results.
             if json_path.is_file()
+                && json_path.parent() != Some(out_dir.as_path())
                 && json_path
                     .extension()
                     .map(|ext| ext.to_str().unwrap() == "json")
@@ -221,30 +440,19 @@ impl Benchmarker {
                     .to_str()
                     .context("to str")?
                     .replace(".json", "");
-                let git_msg = self.commit_message(&git_sha)?;
-                let git_date = self.commit_date(&git_sha)?;
+                let git_msg = self.git()?.commit_message(&git_sha)?;
+                let git_date = self.git()?.commit_date(&git_sha)?;
                 if let Ok(rf) = serde_json::from_reader::<_, ResultFile>(File::open(&json_path)?) {
+                    let environment = rf.environment.map(|e| e.to_string());
                     for res in rf.results {
-                        let command = res.command;
-                        if let Some(times) = res.times {
-                            for time in times {
-                                plotdata.push(PlotData {
-                                    git_sha: git_sha.clone(),
-                                    git_msg: git_msg.clone(),
-                                    git_date: git_date.clone(),
-                                    command: command.clone(),
-                                    time: Some(time),
-                                })
-                            }
-                        } else {
-                            plotdata.push(PlotData {
-                                git_sha: git_sha.clone(),
-                                git_msg: git_msg.clone(),
-                                git_date: git_date.clone(),
-                                command: command.clone(),
-                                time: None,
-                            })
-                        }
+                        by_commit.push(CommitEntry {
+                            git_sha: git_sha.clone(),
+                            git_msg: git_msg.clone(),
+                            git_date: git_date.clone(),
+                            command: res.command,
+                            times: res.times,
+                            environment: environment.clone(),
+                        });
                     }
                 } else {
                     eprintln!("Error deserializing {:?}", json_path);
@@ -252,6 +460,69 @@ impl Benchmarker {
             }
         }
 
+        let mut by_command: HashMap<&str, Vec<Sample>> = HashMap::new();
+        for entry in &by_commit {
+            if let Some(times) = &entry.times {
+                by_command.entry(&entry.command).or_default().push(Sample {
+                    git_sha: &entry.git_sha,
+                    git_date: &entry.git_date,
+                    times,
+                });
+            }
+        }
+
+        let mut regressions = Vec::new();
+        for (command, samples) in by_command {
+            regressions.extend(regression::detect(
+                command,
+                samples,
+                self.regression_p_threshold,
+                self.regression_min_change,
+            ));
+        }
+        let regressed: std::collections::HashSet<(String, String)> = regressions
+            .iter()
+            .map(|r| (r.to_sha.clone(), r.command.clone()))
+            .collect();
+        let mut f = File::create(out_dir.join("regressions.json"))?;
+        write!(f, "{}", serde_json::to_string_pretty(&regressions)?)?;
+
+        let mut plotdata: Vec<PlotData> = Vec::new();
+        for CommitEntry {
+            git_sha,
+            git_msg,
+            git_date,
+            command,
+            times,
+            environment,
+        } in by_commit
+        {
+            let regression = regressed.contains(&(git_sha.clone(), command.clone()));
+            if let Some(times) = times {
+                for time in times {
+                    plotdata.push(PlotData {
+                        git_sha: git_sha.clone(),
+                        git_msg: git_msg.clone(),
+                        git_date: git_date.clone(),
+                        command: command.clone(),
+                        time: Some(time),
+                        regression,
+                        environment: environment.clone(),
+                    })
+                }
+            } else {
+                plotdata.push(PlotData {
+                    git_sha,
+                    git_msg,
+                    git_date,
+                    command,
+                    time: None,
+                    regression,
+                    environment,
+                })
+            }
+        }
+
         let vega_spec = json!(
             {
                 "$schema": "https://vega.github.io/schema/vega-lite/v5.json",
@@ -277,13 +548,20 @@ impl Benchmarker {
                   "tooltip": [
                     {"field": "git_msg", "type": "nominal"},
                     {"field": "git_date", "type": "nominal"},
-                    {"field": "git_sha", "type": "nominal"}
+                    {"field": "git_sha", "type": "nominal"},
+                    {"field": "environment", "type": "nominal"}
                   ],
                   "color": {
-                    "condition": {
-                      "test": "datum['time'] === null",
-                      "value": "#f00"
-                    }
+                    "condition": [
+                      {
+                        "test": "datum['time'] === null",
+                        "value": "#f00"
+                      },
+                      {
+                        "test": "datum['regression']",
+                        "value": "#e8a400"
+                      }
+                    ]
                   },
                   "facet": {
                     "field": "command",
@@ -299,6 +577,19 @@ impl Benchmarker {
     }
 }
 
+/// One `(git_sha, command)` result read back out of `results/**.json`: its
+/// commit metadata, raw times (if any), and machine conditions, so `plot` can
+/// both flatten these into per-point `PlotData` and run the regression pass
+/// per command.
+struct CommitEntry {
+    git_sha: String,
+    git_msg: String,
+    git_date: String,
+    command: String,
+    times: Option<Vec<f64>>,
+    environment: Option<String>,
+}
+
 #[derive(Debug, Serialize)]
 struct PlotData {
     git_sha: String,
@@ -306,14 +597,24 @@ struct PlotData {
     git_date: String,
     command: String,
     time: Option<f64>,
+    /// Whether this commit was flagged as a statistically significant slowdown
+    /// relative to its predecessor, for this command.
+    regression: bool,
+    /// Machine conditions the benchmark ran under, if known, so the tooltip
+    /// can warn when measurements were taken under different setups.
+    environment: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 struct ResultFile {
     results: Vec<ResultEntry>,
+    /// Machine conditions the benchmark ran under, if stabilization hooks
+    /// were configured. Absent for files written before this field existed.
+    #[serde(default)]
+    environment: Option<MachineConditions>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 struct ResultEntry {
     command: String,
     times: Option<Vec<f64>>,
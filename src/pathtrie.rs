@@ -0,0 +1,104 @@
+//! A small trie over `/`-separated path segments, used to cheaply test
+//! whether a changed file falls under one of the configured benchmark paths.
+
+use std::collections::HashMap;
+
+#[derive(Default)]
+struct Node {
+    children: HashMap<String, Node>,
+    terminal: bool,
+}
+
+/// A set of path prefixes (e.g. `src/engine`), organized as a trie so
+/// testing a changed file against all of them only walks as many segments as
+/// it shares with a configured prefix, rather than comparing against each
+/// prefix in turn.
+#[derive(Default)]
+pub struct PathTrie {
+    root: Node,
+}
+
+impl PathTrie {
+    pub fn from_prefixes<I, S>(prefixes: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut trie = Self::default();
+        for prefix in prefixes {
+            trie.insert(prefix.as_ref());
+        }
+        trie
+    }
+
+    fn insert(&mut self, prefix: &str) {
+        let mut node = &mut self.root;
+        for segment in segments(prefix) {
+            node = node.children.entry(segment.to_owned()).or_default();
+        }
+        node.terminal = true;
+    }
+
+    /// True if `path` is under (or equal to) any inserted prefix.
+    pub fn matches(&self, path: &str) -> bool {
+        let mut node = &self.root;
+        if node.terminal {
+            return true;
+        }
+        for segment in segments(path) {
+            node = match node.children.get(segment) {
+                Some(n) => n,
+                None => return false,
+            };
+            if node.terminal {
+                return true;
+            }
+        }
+        false
+    }
+}
+
+fn segments(path: &str) -> impl Iterator<Item = &str> {
+    path.trim_matches('/').split('/').filter(|s| !s.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_a_file_under_a_prefix() {
+        let trie = PathTrie::from_prefixes(["src/engine"]);
+        assert!(trie.matches("src/engine/mod.rs"));
+        assert!(trie.matches("src/engine/sub/deep.rs"));
+        assert!(trie.matches("src/engine"));
+    }
+
+    #[test]
+    fn does_not_match_a_sibling_with_a_shared_prefix_string() {
+        let trie = PathTrie::from_prefixes(["src/engine"]);
+        assert!(!trie.matches("src/enginex"));
+        assert!(!trie.matches("src/enginex/mod.rs"));
+    }
+
+    #[test]
+    fn does_not_match_unrelated_paths() {
+        let trie = PathTrie::from_prefixes(["src/engine"]);
+        assert!(!trie.matches("src/other.rs"));
+        assert!(!trie.matches("README.md"));
+    }
+
+    #[test]
+    fn matches_against_any_of_several_prefixes() {
+        let trie = PathTrie::from_prefixes(["src/engine", "docs"]);
+        assert!(trie.matches("src/engine/mod.rs"));
+        assert!(trie.matches("docs/guide.md"));
+        assert!(!trie.matches("src/other.rs"));
+    }
+
+    #[test]
+    fn empty_trie_matches_nothing() {
+        let trie = PathTrie::from_prefixes(Vec::<String>::new());
+        assert!(!trie.matches("src/engine/mod.rs"));
+    }
+}
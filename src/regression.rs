@@ -0,0 +1,133 @@
+//! Statistical regression detection between adjacent commits.
+
+use serde_derive::Serialize;
+
+use crate::stats::{mean, variance, welch_t_test};
+
+/// A single benchmarked commit: its samples for one command, in commit order.
+pub struct Sample<'a> {
+    pub git_sha: &'a str,
+    pub git_date: &'a str,
+    pub times: &'a [f64],
+}
+
+/// A detected regression between two adjacent commits for a given command.
+#[derive(Debug, Serialize)]
+pub struct Regression {
+    pub command: String,
+    pub from_sha: String,
+    pub to_sha: String,
+    pub percent_change: f64,
+    pub p_value: f64,
+}
+
+/// Compares each adjacent pair of commits (ordered by `git_date`) for a single
+/// command's samples and flags the ones whose slowdown is both statistically
+/// significant (`p_value < p_threshold`) and large enough to matter
+/// (`percent_change > min_change`).
+pub fn detect(command: &str, mut samples: Vec<Sample>, p_threshold: f64, min_change: f64) -> Vec<Regression> {
+    samples.sort_by(|a, b| a.git_date.cmp(b.git_date));
+
+    let mut regressions = Vec::new();
+    for pair in samples.windows(2) {
+        let (prev, cur) = (&pair[0], &pair[1]);
+        if prev.times.len() < 2 || cur.times.len() < 2 {
+            continue;
+        }
+
+        let m1 = mean(prev.times);
+        let m2 = mean(cur.times);
+        let percent_change = (m2 - m1) / m1;
+
+        let Some(test) = welch_t_test(
+            m1,
+            variance(prev.times),
+            prev.times.len(),
+            m2,
+            variance(cur.times),
+            cur.times.len(),
+        ) else {
+            continue;
+        };
+
+        if test.p_value < p_threshold && percent_change > min_change {
+            regressions.push(Regression {
+                command: command.to_owned(),
+                from_sha: prev.git_sha.to_owned(),
+                to_sha: cur.git_sha.to_owned(),
+                percent_change,
+                p_value: test.p_value,
+            });
+        }
+    }
+    regressions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_a_significant_slowdown_between_adjacent_commits() {
+        let fast = [1.0, 1.01, 0.99, 1.0, 1.02, 0.98];
+        let slow = [2.0, 2.01, 1.99, 2.0, 2.02, 1.98];
+        let samples = vec![
+            Sample {
+                git_sha: "a",
+                git_date: "2024-01-01",
+                times: &fast,
+            },
+            Sample {
+                git_sha: "b",
+                git_date: "2024-01-02",
+                times: &slow,
+            },
+        ];
+
+        let regressions = detect("bench", samples, 0.05, 0.1);
+        assert_eq!(regressions.len(), 1);
+        assert_eq!(regressions[0].from_sha, "a");
+        assert_eq!(regressions[0].to_sha, "b");
+        assert!(regressions[0].percent_change > 0.1);
+    }
+
+    #[test]
+    fn ignores_a_change_below_min_change() {
+        let base = [1.0, 1.0001, 0.9999, 1.0, 1.0002, 0.9998];
+        let barely_slower = [1.001, 1.0011, 1.0009, 1.001, 1.0012, 1.0008];
+        let samples = vec![
+            Sample {
+                git_sha: "a",
+                git_date: "2024-01-01",
+                times: &base,
+            },
+            Sample {
+                git_sha: "b",
+                git_date: "2024-01-02",
+                times: &barely_slower,
+            },
+        ];
+
+        assert!(detect("bench", samples, 0.05, 0.1).is_empty());
+    }
+
+    #[test]
+    fn skips_pairs_with_too_few_samples() {
+        let one = [1.0];
+        let two = [1.0, 2.0];
+        let samples = vec![
+            Sample {
+                git_sha: "a",
+                git_date: "2024-01-01",
+                times: &one,
+            },
+            Sample {
+                git_sha: "b",
+                git_date: "2024-01-02",
+                times: &two,
+            },
+        ];
+
+        assert!(detect("bench", samples, 0.05, 0.0).is_empty());
+    }
+}
@@ -0,0 +1,109 @@
+//! Shell-style tokenizing and `PATH` resolution for `prepare`/`benchmark`
+//! command strings. `str::split_whitespace` mangles any argument containing
+//! spaces or quotes, and resolving a bare program name by letting the OS
+//! search it from the cloned repository's working directory risks running
+//! a file an untrusted repo dropped there instead of a trusted system tool.
+
+use anyhow::{bail, Context, Result};
+use std::path::{Path, PathBuf};
+
+/// Splits `line` into argv-style tokens, honoring single/double quotes and
+/// backslash escapes the way a POSIX shell would.
+pub fn tokenize(line: &str) -> Result<Vec<String>> {
+    shell_words::split(line).with_context(|| format!("parsing command line: {:?}", line))
+}
+
+/// Resolves `program` to the file that would run.
+///
+/// If `program` contains a path separator it's used as-is — the caller meant
+/// a specific file, e.g. a repo-relative `./build.sh`. Otherwise it's looked
+/// up in `PATH`, skipping empty and `.` entries so a file the cloned
+/// repository happens to drop in the working directory can't masquerade as
+/// a system tool of the same name.
+pub fn resolve(program: &str) -> Result<PathBuf> {
+    if program.contains(std::path::MAIN_SEPARATOR) {
+        return Ok(PathBuf::from(program));
+    }
+
+    let path = std::env::var_os("PATH").context("PATH is not set")?;
+    for dir in std::env::split_paths(&path) {
+        if dir.as_os_str().is_empty() || dir == Path::new(".") {
+            continue;
+        }
+        let candidate = dir.join(program);
+        if candidate.is_file() {
+            return Ok(candidate);
+        }
+    }
+    bail!("{:?} not found on PATH", program)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokenize_splits_on_whitespace() {
+        assert_eq!(tokenize("cargo build --release").unwrap(), vec!["cargo", "build", "--release"]);
+    }
+
+    #[test]
+    fn tokenize_honors_quoted_arguments_with_embedded_spaces() {
+        assert_eq!(
+            tokenize(r#"echo "hello world" 'and this'"#).unwrap(),
+            vec!["echo", "hello world", "and this"]
+        );
+    }
+
+    #[test]
+    fn tokenize_honors_backslash_escapes() {
+        assert_eq!(tokenize(r"echo foo\ bar").unwrap(), vec!["echo", "foo bar"]);
+    }
+
+    #[test]
+    fn tokenize_rejects_unbalanced_quotes() {
+        assert!(tokenize(r#"echo "unterminated"#).is_err());
+    }
+
+    #[test]
+    fn resolve_passes_through_a_path_qualified_program_unchanged() {
+        let resolved = resolve("./build.sh").unwrap();
+        assert_eq!(resolved, Path::new("./build.sh"));
+    }
+
+    #[test]
+    fn resolve_finds_a_bare_name_on_path() {
+        let dir = tempdir::TempDir::new("fineregr-shellcmd-test").unwrap();
+        let tool = dir.path().join("mytool");
+        std::fs::write(&tool, "").unwrap();
+
+        let original_path = std::env::var_os("PATH");
+        std::env::set_var("PATH", dir.path());
+        let result = resolve("mytool");
+        match original_path {
+            Some(p) => std::env::set_var("PATH", p),
+            None => std::env::remove_var("PATH"),
+        }
+
+        assert_eq!(result.unwrap(), tool);
+    }
+
+    #[test]
+    fn resolve_skips_dot_and_empty_path_entries() {
+        let dir = tempdir::TempDir::new("fineregr-shellcmd-test").unwrap();
+        let tool = dir.path().join("mytool");
+        std::fs::write(&tool, "").unwrap();
+
+        // `.` and the empty entry both implicitly mean the current directory,
+        // which must never be trusted to resolve a bare program name.
+        let original_path = std::env::var_os("PATH");
+        std::env::set_var("PATH", format!(".::{}", dir.path().display()));
+        let result = resolve("mytool");
+        match original_path {
+            Some(p) => std::env::set_var("PATH", p),
+            None => std::env::remove_var("PATH"),
+        }
+
+        assert_eq!(result.unwrap(), tool);
+    }
+}
@@ -0,0 +1,92 @@
+//! Small statistics helpers used by the regression-detection pass.
+
+use statrs::distribution::{ContinuousCDF, StudentsT};
+
+/// Sample mean.
+pub fn mean(xs: &[f64]) -> f64 {
+    xs.iter().sum::<f64>() / xs.len() as f64
+}
+
+/// Sample variance (Bessel-corrected, i.e. divided by `n - 1`).
+pub fn variance(xs: &[f64]) -> f64 {
+    let m = mean(xs);
+    xs.iter().map(|x| (x - m).powi(2)).sum::<f64>() / (xs.len() - 1) as f64
+}
+
+/// Outcome of a Welch's t-test between two independent samples.
+#[derive(Debug, Clone, Copy)]
+pub struct WelchTTest {
+    /// One-sided p-value for the hypothesis that the second sample's mean is
+    /// greater than the first's.
+    pub p_value: f64,
+}
+
+/// Welch's t-test for samples with unequal variance, given their means,
+/// variances and sizes. Returns `None` when either sample is too small to
+/// estimate a variance (`n < 2`).
+pub fn welch_t_test(m1: f64, s1: f64, n1: usize, m2: f64, s2: f64, n2: usize) -> Option<WelchTTest> {
+    if n1 < 2 || n2 < 2 {
+        return None;
+    }
+    let n1 = n1 as f64;
+    let n2 = n2 as f64;
+    let se1 = s1 / n1;
+    let se2 = s2 / n2;
+    let se_sum = se1 + se2;
+    if se_sum <= 0.0 {
+        return None;
+    }
+
+    let t = (m2 - m1) / se_sum.sqrt();
+    let df = se_sum.powi(2) / (se1.powi(2) / (n1 - 1.0) + se2.powi(2) / (n2 - 1.0));
+
+    let dist = StudentsT::new(0.0, 1.0, df).ok()?;
+    // One-sided: probability of seeing a t-statistic at least this large if the
+    // two samples actually had the same mean.
+    let p_value = 1.0 - dist.cdf(t);
+
+    Some(WelchTTest { p_value })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mean_and_variance_known_values() {
+        let xs = [2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0];
+        assert_eq!(mean(&xs), 5.0);
+        assert!((variance(&xs) - 4.571428571428571).abs() < 1e-9);
+    }
+
+    #[test]
+    fn welch_t_test_identical_samples_is_not_significant() {
+        let xs = [1.0, 1.0, 1.0, 1.0];
+        let test = welch_t_test(mean(&xs), variance(&xs), xs.len(), mean(&xs), variance(&xs), xs.len());
+        // Equal, zero-variance samples give a zero/NaN standard error, which
+        // `welch_t_test` treats as "can't test" rather than a spurious p=0.
+        assert!(test.is_none());
+    }
+
+    #[test]
+    fn welch_t_test_detects_a_clear_slowdown() {
+        let before = [1.0, 1.01, 0.99, 1.0, 1.02, 0.98];
+        let after = [2.0, 2.01, 1.99, 2.0, 2.02, 1.98];
+        let test = welch_t_test(
+            mean(&before),
+            variance(&before),
+            before.len(),
+            mean(&after),
+            variance(&after),
+            after.len(),
+        )
+        .expect("both samples have n >= 2");
+        assert!(test.p_value < 0.001);
+    }
+
+    #[test]
+    fn welch_t_test_needs_at_least_two_samples_per_side() {
+        assert!(welch_t_test(1.0, 0.1, 1, 2.0, 0.1, 5).is_none());
+        assert!(welch_t_test(1.0, 0.1, 5, 2.0, 0.1, 1).is_none());
+    }
+}